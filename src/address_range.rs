@@ -0,0 +1,40 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RangeType {
+    Contents,
+    NoContents,
+    Ignore,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct AddressRange {
+    pub from: u32,
+    pub to: u32,
+    pub range_type: RangeType,
+}
+
+impl AddressRange {
+    pub const fn new(from: u32, to: u32, range_type: RangeType) -> AddressRange {
+        AddressRange { from, to, range_type }
+    }
+
+    pub fn contains(&self, addr: u32) -> bool {
+        addr >= self.from && addr < self.to
+    }
+}
+
+pub const MAIN_RAM_START: u32 = 0x2000_0000;
+pub const MAIN_RAM_END: u32 = 0x2004_2000;
+pub const FLASH_START: u32 = 0x1000_0000;
+pub const FLASH_END: u32 = 0x1500_0000;
+pub const XIP_SRAM_START: u32 = 0x1500_0000;
+pub const XIP_SRAM_END: u32 = 0x1500_4000;
+
+pub const RP2040_ADDRESS_RANGES_FLASH: &[AddressRange] = &[
+    AddressRange::new(FLASH_START, FLASH_END, RangeType::Contents),
+    AddressRange::new(MAIN_RAM_START, MAIN_RAM_END, RangeType::NoContents),
+];
+
+pub const RP2040_ADDRESS_RANGES_RAM: &[AddressRange] = &[
+    AddressRange::new(MAIN_RAM_START, MAIN_RAM_END, RangeType::Contents),
+    AddressRange::new(XIP_SRAM_START, XIP_SRAM_END, RangeType::Contents),
+];
@@ -0,0 +1,192 @@
+use crate::elf::{realize_page, Pages, PAGE_SIZE};
+use crate::uf2::Uf2BlockData;
+use std::{
+    error::Error,
+    io::{Read, Seek},
+};
+
+const MARKER_START: u32 = 0x7188_ebf2;
+const MARKER_END: u32 = 0xe71a_a390;
+
+const TYPE_ID_AND_INT: u16 = 5;
+const TYPE_ID_AND_STRING: u16 = 6;
+
+const ID_PROGRAM_NAME: u32 = 0x02031c86;
+const ID_PROGRAM_VERSION_STRING: u32 = 0x11a9bc3a;
+const ID_PROGRAM_BUILD_DATE_STRING: u32 = 0x9da22254;
+const ID_PROGRAM_DESCRIPTION: u32 = 0xb6a07c2c;
+const ID_PROGRAM_URL: u32 = 0x725a5000;
+const ID_PICO_BOARD: u32 = 0xb63cffbb;
+const ID_SDK_VERSION: u32 = 0x5360b3ab;
+
+fn field_name(id: u32) -> &'static str {
+    match id {
+        ID_PROGRAM_NAME => "program_name",
+        ID_PROGRAM_VERSION_STRING => "program_version_string",
+        ID_PROGRAM_BUILD_DATE_STRING => "program_build_date_string",
+        ID_PROGRAM_DESCRIPTION => "program_description",
+        ID_PROGRAM_URL => "program_url",
+        ID_PICO_BOARD => "pico_board",
+        ID_SDK_VERSION => "sdk_version",
+        _ => "unknown",
+    }
+}
+
+/// Translates addresses pointing into the RAM copy of a `.data`-style
+/// binary_info table/string back into the flash image, assuming the RAM
+/// region mirrors the flash bytes starting at the binary_info header
+/// byte-for-byte (true for the SDK's default startup copy loop).
+struct RamMapping {
+    ram_base: u32,
+    flash_base: u32,
+}
+
+/// A contiguous in-memory copy of every page `elf2uf2` would otherwise have
+/// streamed straight into the UF2 output, indexed by its lowest target address.
+struct Image {
+    base: u32,
+    bytes: Vec<u8>,
+    ram_mapping: Option<RamMapping>,
+}
+
+impl Image {
+    fn build(input: &mut (impl Read + Seek), pages: &Pages) -> Result<Image, Box<dyn Error>> {
+        let base = *pages.keys().next().ok_or("The input file has no memory pages")?;
+        let end = *pages.keys().last().unwrap() + PAGE_SIZE;
+
+        let mut bytes = vec![0u8; (end - base) as usize];
+
+        for (page_addr, fragments) in pages {
+            let mut block_data: Uf2BlockData = [0; 476];
+            realize_page(input, fragments, &mut block_data)?;
+
+            let offset = (page_addr - base) as usize;
+            bytes[offset..offset + PAGE_SIZE as usize]
+                .copy_from_slice(&block_data[..PAGE_SIZE as usize]);
+        }
+
+        Ok(Image { base, bytes, ram_mapping: None })
+    }
+
+    fn flash_offset_of(&self, addr: u32) -> Option<usize> {
+        if addr < self.base {
+            return None;
+        }
+        let offset = (addr - self.base) as usize;
+        (offset < self.bytes.len()).then_some(offset)
+    }
+
+    fn offset_of(&self, addr: u32) -> Option<usize> {
+        if let Some(offset) = self.flash_offset_of(addr) {
+            return Some(offset);
+        }
+
+        let mapping = self.ram_mapping.as_ref()?;
+        if addr < mapping.ram_base {
+            return None;
+        }
+        let flash_addr = mapping.flash_base + (addr - mapping.ram_base);
+        self.flash_offset_of(flash_addr)
+    }
+
+    fn read_u32(&self, addr: u32) -> Option<u32> {
+        let offset = self.offset_of(addr)?;
+        let word = self.bytes.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(word.try_into().unwrap()))
+    }
+
+    fn read_u16(&self, addr: u32) -> Option<u16> {
+        let offset = self.offset_of(addr)?;
+        let word = self.bytes.get(offset..offset + 2)?;
+        Some(u16::from_le_bytes(word.try_into().unwrap()))
+    }
+
+    fn read_cstr(&self, addr: u32) -> Option<String> {
+        let offset = self.offset_of(addr)?;
+        let nul = self.bytes[offset..].iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&self.bytes[offset..offset + nul]).into_owned())
+    }
+}
+
+struct BinaryInfoHeader {
+    table_start: u32,
+    table_end: u32,
+    ram_mapping: Option<RamMapping>,
+}
+
+fn find_binary_info_header(image: &Image) -> Option<BinaryInfoHeader> {
+    let mut addr = image.base;
+    while image.offset_of(addr + 16).is_some() {
+        if image.read_u32(addr) == Some(MARKER_START) && image.read_u32(addr + 16) == Some(MARKER_END) {
+            let table_start = image.read_u32(addr + 4)?;
+            let table_end = image.read_u32(addr + 8)?;
+            let ram_copy_base = image.read_u32(addr + 12)?;
+
+            // a zero base means the table/strings are flash-resident already
+            let ram_mapping = (ram_copy_base != 0).then_some(RamMapping {
+                ram_base: ram_copy_base,
+                flash_base: addr,
+            });
+
+            return Some(BinaryInfoHeader { table_start, table_end, ram_mapping });
+        }
+        addr += 4;
+    }
+    None
+}
+
+fn print_entry(image: &Image, entry_ptr: u32) {
+    let Some(ty) = image.read_u16(entry_ptr) else {
+        println!("(unresolved binary_info entry at {:#08x})", entry_ptr);
+        return;
+    };
+    let Some(_tag) = image.read_u16(entry_ptr + 2) else {
+        println!("(unresolved binary_info entry at {:#08x})", entry_ptr);
+        return;
+    };
+
+    match ty {
+        TYPE_ID_AND_STRING => {
+            match (image.read_u32(entry_ptr + 4), image.read_u32(entry_ptr + 8)) {
+                (Some(id), Some(str_ptr)) => match image.read_cstr(str_ptr) {
+                    Some(value) => println!("{}: {}", field_name(id), value),
+                    None => println!("{}: (unresolved string at {:#08x})", field_name(id), str_ptr),
+                },
+                _ => println!("(unresolved binary_info entry at {:#08x})", entry_ptr),
+            }
+        }
+        TYPE_ID_AND_INT => {
+            match (image.read_u32(entry_ptr + 4), image.read_u32(entry_ptr + 8)) {
+                (Some(id), Some(value)) => println!("{}: {}", field_name(id), value),
+                _ => println!("(unresolved binary_info entry at {:#08x})", entry_ptr),
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Reads the embedded Pico SDK `binary_info` block out of an ELF's loaded
+/// flash/RAM image and prints the program metadata it declares.
+pub fn print_binary_info(
+    input: &mut (impl Read + Seek),
+    pages: &Pages,
+) -> Result<(), Box<dyn Error>> {
+    let mut image = Image::build(input, pages)?;
+
+    let Some(header) = find_binary_info_header(&image) else {
+        return Err("No binary info header found".into());
+    };
+
+    image.ram_mapping = header.ram_mapping;
+
+    let mut addr = header.table_start;
+    while addr < header.table_end {
+        match image.read_u32(addr) {
+            Some(entry_ptr) => print_entry(&image, entry_ptr),
+            None => println!("(unresolved binary_info table entry at {:#08x})", addr),
+        }
+        addr += 4;
+    }
+
+    Ok(())
+}
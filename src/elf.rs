@@ -0,0 +1,178 @@
+use crate::address_range::{AddressRange, RangeType};
+use crate::uf2::Uf2BlockData;
+use assert_into::AssertInto;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    io::{Read, Seek, SeekFrom},
+};
+
+pub const PAGE_SIZE: u32 = 256;
+
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+const PT_LOAD: u32 = 1;
+
+#[derive(Debug)]
+pub struct Elf32Header {
+    pub entry: u32,
+    pub phoff: u32,
+    pub phnum: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PageFragment {
+    pub file_offset: u32,
+    pub page_offset: u32,
+    pub len: u32,
+}
+
+pub type Pages = BTreeMap<u32, Vec<PageFragment>>;
+
+fn read_u16(input: &mut impl Read) -> Result<u16, Box<dyn Error>> {
+    let mut buf = [0; 2];
+    input.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(input: &mut impl Read) -> Result<u32, Box<dyn Error>> {
+    let mut buf = [0; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub fn read_and_check_elf32_header(
+    input: &mut (impl Read + Seek),
+) -> Result<Elf32Header, Box<dyn Error>> {
+    input.seek(SeekFrom::Start(0))?;
+
+    let mut e_ident = [0; 16];
+    input.read_exact(&mut e_ident)?;
+
+    if e_ident[0..4] != ELF_MAGIC {
+        return Err("Not a valid ELF file".into());
+    }
+
+    if e_ident[4] != 1 {
+        return Err("Not a 32 bit executable".into());
+    }
+
+    if e_ident[5] != 1 {
+        return Err("Not a little-endian executable".into());
+    }
+
+    let _e_type = read_u16(input)?;
+    let _e_machine = read_u16(input)?;
+    let _e_version = read_u32(input)?;
+    let entry = read_u32(input)?;
+    let phoff = read_u32(input)?;
+    let _shoff = read_u32(input)?;
+    let _flags = read_u32(input)?;
+    let _ehsize = read_u16(input)?;
+    let _phentsize = read_u16(input)?;
+    let phnum = read_u16(input)?;
+
+    Ok(Elf32Header { entry, phoff, phnum })
+}
+
+struct Elf32PhEntry {
+    p_type: u32,
+    p_offset: u32,
+    p_paddr: u32,
+    p_filesz: u32,
+}
+
+fn read_ph_entry(input: &mut impl Read) -> Result<Elf32PhEntry, Box<dyn Error>> {
+    let p_type = read_u32(input)?;
+    let p_offset = read_u32(input)?;
+    let _p_vaddr = read_u32(input)?;
+    let p_paddr = read_u32(input)?;
+    let p_filesz = read_u32(input)?;
+    let _p_memsz = read_u32(input)?;
+    let _p_flags = read_u32(input)?;
+    let _p_align = read_u32(input)?;
+
+    Ok(Elf32PhEntry {
+        p_type,
+        p_offset,
+        p_paddr,
+        p_filesz,
+    })
+}
+
+pub fn read_and_check_elf32_ph_entries(
+    input: &mut (impl Read + Seek),
+    eh: &Elf32Header,
+    valid_ranges: &[AddressRange],
+) -> Result<Pages, Box<dyn Error>> {
+    let mut pages = Pages::new();
+
+    for i in 0..eh.phnum {
+        input.seek(SeekFrom::Start(
+            eh.phoff as u64 + (i as u64) * 32,
+        ))?;
+        let ph = read_ph_entry(input)?;
+
+        if ph.p_type != PT_LOAD || ph.p_filesz == 0 {
+            continue;
+        }
+
+        let from = ph.p_paddr;
+        let to = ph.p_paddr + ph.p_filesz;
+
+        let range = valid_ranges
+            .iter()
+            .find(|r| r.contains(from) && (to == r.to || r.contains(to - 1)));
+
+        let range = match range {
+            Some(range) => range,
+            None => {
+                return Err(format!(
+                    "Memory segment 0x{:08x}-0x{:08x} is outside of the valid address range for the selected target",
+                    from, to
+                )
+                .into())
+            }
+        };
+
+        if range.range_type != RangeType::Contents {
+            continue;
+        }
+
+        let mut page_addr = ph.p_paddr;
+        let mut file_offset = ph.p_offset;
+        let mut remaining = ph.p_filesz;
+
+        while remaining > 0 {
+            let page_base = page_addr & !(PAGE_SIZE - 1);
+            let page_offset = page_addr - page_base;
+            let take = remaining.min(PAGE_SIZE - page_offset);
+
+            pages.entry(page_base).or_insert_with(Vec::new).push(PageFragment {
+                file_offset,
+                page_offset,
+                len: take,
+            });
+
+            page_addr += take;
+            file_offset += take;
+            remaining -= take;
+        }
+    }
+
+    Ok(pages)
+}
+
+pub fn realize_page(
+    input: &mut (impl Read + Seek),
+    fragments: &[PageFragment],
+    block_data: &mut Uf2BlockData,
+) -> Result<(), Box<dyn Error>> {
+    for fragment in fragments {
+        input.seek(SeekFrom::Start(fragment.file_offset as u64))?;
+        let start: usize = fragment.page_offset.assert_into();
+        let end: usize = (fragment.page_offset + fragment.len).assert_into();
+        input.read_exact(&mut block_data[start..end])?;
+    }
+
+    Ok(())
+}
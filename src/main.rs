@@ -1,4 +1,4 @@
-use address_range::{MAIN_RAM_START, RP2040_ADDRESS_RANGES_FLASH, RP2040_ADDRESS_RANGES_RAM};
+use address_range::MAIN_RAM_START;
 use assert_into::AssertInto;
 use clap::Parser;
 use elf::{read_and_check_elf32_ph_entries, realize_page, PAGE_SIZE};
@@ -9,20 +9,24 @@ use static_assertions::const_assert;
 use std::{
     error::Error,
     fs::{self, File},
-    io::{self, BufReader, Read, Seek, Write},
+    io::{self, BufRead, BufReader, Read, Seek, Write},
     path::{Path, PathBuf},
     thread,
     time::Duration,
 };
 use sysinfo::{DiskExt, SystemExt};
+use target::Target;
 use uf2::{
-    Uf2BlockData, Uf2BlockFooter, Uf2BlockHeader, RP2040_FAMILY_ID, UF2_FLAG_FAMILY_ID_PRESENT,
-    UF2_MAGIC_END, UF2_MAGIC_START0, UF2_MAGIC_START1,
+    Uf2BlockData, Uf2BlockFooter, Uf2BlockHeader, UF2_FLAG_FAMILY_ID_PRESENT, UF2_MAGIC_END,
+    UF2_MAGIC_START0, UF2_MAGIC_START1,
 };
 use zerocopy::AsBytes;
 
 mod address_range;
+mod binary_info;
 mod elf;
+mod picoboot;
+mod target;
 mod uf2;
 
 #[derive(Parser, Debug)]
@@ -36,10 +40,30 @@ struct Opts {
     #[clap(short, long)]
     deploy: bool,
 
+    /// Deploy directly over the PICOBOOT USB interface, without a mounted drive
+    #[clap(long)]
+    deploy_usb: bool,
+
     /// Connect to serial after deploy
     #[clap(short, long)]
     serial: bool,
 
+    /// Target chip/image family to produce the UF2 for
+    #[clap(short, long, value_enum, default_value = "rp2040")]
+    target: Target,
+
+    /// Print the embedded Pico SDK binary_info instead of writing a UF2
+    #[clap(long)]
+    info: bool,
+
+    /// Reboot a pico running a USB-serial application into BOOTSEL before deploying
+    #[clap(long)]
+    bootsel_reset: bool,
+
+    /// Baud rate used for the --serial terminal
+    #[clap(long, default_value_t = 115200)]
+    baud: u32,
+
     /// Input file
     input: String,
 
@@ -76,11 +100,7 @@ fn elf2uf2(mut input: impl Read + Seek, mut output: impl Write) -> Result<(), Bo
         }
     }
 
-    let valid_ranges = if ram_style {
-        RP2040_ADDRESS_RANGES_RAM
-    } else {
-        RP2040_ADDRESS_RANGES_FLASH
-    };
+    let valid_ranges = Opts::global().target.address_ranges(ram_style);
 
     let pages = read_and_check_elf32_ph_entries(&mut input, &eh, valid_ranges)?;
 
@@ -109,7 +129,7 @@ fn elf2uf2(mut input: impl Read + Seek, mut output: impl Write) -> Result<(), Bo
         payload_size: PAGE_SIZE,
         block_no: 0,
         num_blocks: pages.len().assert_into(),
-        file_size: RP2040_FAMILY_ID,
+        file_size: Opts::global().target.family_id(),
     };
 
     let mut block_data: Uf2BlockData = [0; 476];
@@ -173,8 +193,63 @@ fn elf2uf2(mut input: impl Read + Seek, mut output: impl Write) -> Result<(), Bo
     Ok(())
 }
 
+fn elf_pages(input: &mut (impl Read + Seek)) -> Result<(elf::Pages, bool), Box<dyn Error>> {
+    let eh = elf::read_and_check_elf32_header(input)?;
+
+    let ram_style = 0x2 == eh.entry >> 28;
+    let valid_ranges = Opts::global().target.address_ranges(ram_style);
+
+    let pages = read_and_check_elf32_ph_entries(input, &eh, valid_ranges)?;
+
+    if pages.is_empty() {
+        return Err("The input file has no memory pages".into());
+    }
+
+    Ok((pages, ram_style))
+}
+
+fn info(mut input: impl Read + Seek) -> Result<(), Box<dyn Error>> {
+    let (pages, _ram_style) = elf_pages(&mut input)?;
+
+    binary_info::print_binary_info(&mut input, &pages)
+}
+
+fn deploy_usb(mut input: impl Read + Seek) -> Result<(), Box<dyn Error>> {
+    let (pages, ram_style) = elf_pages(&mut input)?;
+
+    picoboot::deploy(&mut input, &pages, !ram_style)
+}
+
+fn pico_serial_port_by_vid_pid() -> Option<SerialPortInfo> {
+    // list of known pico USB vid/pid
+    let pico_usb_ref = [UsbPortInfo {
+        vid: 0x16c0,
+        pid: 0x27dd,
+        // the following fields are not used in the check
+        serial_number: None,
+        manufacturer: None,
+        product: None,
+    }];
+
+    // loop over all the found serial port's) to find a USB one that fits
+    for port in serialport::available_ports().unwrap() {
+        match port.port_type {
+            UsbPort(ref p) => {
+                for p_ref in &pico_usb_ref {
+                    if p.vid == p_ref.vid && p.pid == p_ref.pid {
+                        println!("Found pico serial on {}", &port.port_name);
+                        return Some(port);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 fn available_serial_port(serial_ports_before: Vec<SerialPortInfo>) -> Option<SerialPortInfo> {
-    if Opts::global().deploy {
+    if Opts::global().deploy || Opts::global().deploy_usb {
         // delay counter to wait for the serial port to pop up once the pico rebooted
         for _ in 0..10 {
             if let Ok(available_ports) = serialport::available_ports() {
@@ -191,68 +266,153 @@ fn available_serial_port(serial_ports_before: Vec<SerialPortInfo>) -> Option<Ser
             thread::sleep(Duration::from_millis(200));
         }
 
-        return None;
+        None
     } else {
-        // list of known pico USB vid/pid
-        let pico_usb_ref = [UsbPortInfo {
-            vid: 0x16c0,
-            pid: 0x27dd,
-            // the following fields are not used in the check
-            serial_number: None,
-            manufacturer: None,
-            product: None,
-        }];
-
-        // loop over all the found serial port's) to find a USB one that fits
-        for port in serialport::available_ports().unwrap() {
-            match port.port_type {
-                UsbPort(ref p) => {
-                    for p_ref in &pico_usb_ref {
-                        if p.vid == p_ref.vid && p.pid == p_ref.pid {
-                            println!("Found pico serial on {}", &port.port_name);
-                            return Some(port);
-                        }
-                    }
-                }
-                _ => {}
+        pico_serial_port_by_vid_pid()
+    }
+}
+
+// Reboots a pico running a USB-serial application into BOOTSEL by touching
+// its serial port at 1200 baud and asserting then dropping DTR, the same
+// handshake the Pico SDK's stdio_usb reset code watches for.
+fn bootsel_reset() -> Result<(), Box<dyn Error>> {
+    let port_info = pico_serial_port_by_vid_pid()
+        .ok_or("Unable to find a pico serial port to reset into BOOTSEL")?;
+
+    println!("Resetting pico on {} into BOOTSEL", &port_info.port_name);
+
+    let mut port = serialport::new(&port_info.port_name, 1200)
+        .timeout(Duration::from_millis(100))
+        .open()?;
+
+    port.write_data_terminal_ready(true)?;
+    port.write_data_terminal_ready(false)?;
+    drop(port);
+
+    Ok(())
+}
+
+fn find_pico_drive(retry: bool) -> Option<PathBuf> {
+    // a freshly reset pico takes a moment to enumerate and mount, so retry
+    // a few times when we just asked it to reboot into BOOTSEL
+    let attempts = if retry { 10 } else { 1 };
+
+    for attempt in 0..attempts {
+        let sys = sysinfo::System::new_all();
+
+        for disk in sys.disks() {
+            let mount = disk.mount_point();
+
+            if mount.join("INFO_UF2.TXT").is_file() {
+                return Some(mount.to_owned());
             }
         }
-        return None;
+
+        if attempt + 1 != attempts {
+            thread::sleep(Duration::from_millis(500));
+        }
     }
+
+    None
 }
 
 fn serial_comm(serial_ports_before: Vec<serialport::SerialPortInfo>) -> Result<(), Box<dyn Error>> {
-    if let Some(serial_port_info) = available_serial_port(serial_ports_before) {
-        for _ in 0..5 {
-            if let Ok(mut port) = serialport::new(&serial_port_info.port_name, 115200)
-                .timeout(Duration::from_millis(100))
-                .flow_control(FlowControl::Hardware)
-                .open()
-            {
-                if port.write_data_terminal_ready(true).is_ok() {
-                    let mut serial_buf = [0; 1024];
-                    loop {
-                        match port.read(&mut serial_buf) {
-                            Ok(t) => {
-                                io::stdout().write_all(&serial_buf[..t])?;
-                                io::stdout().flush()?;
-                            }
-                            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => (),
-                            Err(e) => return Err(e.into()),
-                        }
-                    }
+    let Some(serial_port_info) = available_serial_port(serial_ports_before) else {
+        return Ok(());
+    };
+
+    let mut port = None;
+    for _ in 0..5 {
+        if let Ok(p) = serialport::new(&serial_port_info.port_name, Opts::global().baud)
+            .timeout(Duration::from_millis(100))
+            .flow_control(FlowControl::Hardware)
+            .open()
+        {
+            port = Some(p);
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(200));
+    }
+
+    let Some(mut port) = port else {
+        return Err("Unable to open pico serial port".into());
+    };
+
+    port.write_data_terminal_ready(true)?;
+
+    // second handle on the port for the writer thread; this thread keeps
+    // reading port -> stdout below
+    let mut writer = port.try_clone()?;
+
+    // writer thread: stdin -> port, so the terminal is full-duplex
+    let writer_thread = thread::spawn(move || -> io::Result<()> {
+        let stdin = io::stdin();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                // Ctrl-D / EOF on stdin: stop feeding the port
+                return Ok(());
+            }
+            writer.write_all(line.as_bytes())?;
+        }
+    });
+
+    let mut serial_buf = [0; 1024];
+    let read_result: Result<(), Box<dyn Error>> = loop {
+        match port.read(&mut serial_buf) {
+            Ok(0) => break Ok(()),
+            Ok(t) => {
+                if let Err(e) = io::stdout().write_all(&serial_buf[..t]).and_then(|_| io::stdout().flush()) {
+                    break Err(e.into());
                 }
             }
-
-            thread::sleep(Duration::from_millis(200));
+            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {
+                if writer_thread.is_finished() {
+                    break Ok(());
+                }
+            }
+            Err(e) => break Err(e.into()),
         }
+    };
+
+    match writer_thread.join() {
+        Ok(Ok(())) => read_result,
+        Ok(Err(e)) => read_result.and(Err(e.into())),
+        Err(_) => read_result.and(Err("Serial writer thread panicked".into())),
     }
-    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     OPTS.set(Opts::parse()).unwrap();
 
+    if Opts::global().info {
+        let input = BufReader::new(File::open(&Opts::global().input)?);
+        return info(input);
+    }
+
+    if Opts::global().deploy_usb {
+        if Opts::global().bootsel_reset {
+            bootsel_reset()?;
+            // give the pico a moment to re-enumerate in BOOTSEL mode
+            thread::sleep(Duration::from_secs(1));
+        }
+
+        let serial_ports_before = serialport::available_ports()?;
+
+        let input = BufReader::new(File::open(&Opts::global().input)?);
+        deploy_usb(input)?;
+
+        if Opts::global().serial {
+            // New line after "Rebooting pico"
+            println!();
+            serial_comm(serial_ports_before)?;
+        }
+
+        return Ok(());
+    }
+
     // save list of possible serial ports
     // when the deployment is done, a new one shall pop up
     // that is the pico after rebooting
@@ -261,20 +421,12 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut deployed_path = None;
 
     let output = if Opts::global().deploy {
-        let sys = sysinfo::System::new_all();
-
-        let mut pico_drive = None;
-        for disk in sys.disks() {
-            let mount = disk.mount_point();
-
-            if mount.join("INFO_UF2.TXT").is_file() {
-                println!("Found pico uf2 disk {}", &mount.to_string_lossy());
-                pico_drive = Some(mount.to_owned());
-                break;
-            }
+        if Opts::global().bootsel_reset {
+            bootsel_reset()?;
         }
 
-        if let Some(pico_drive) = pico_drive {
+        if let Some(pico_drive) = find_pico_drive(Opts::global().bootsel_reset) {
+            println!("Found pico uf2 disk {}", &pico_drive.to_string_lossy());
             deployed_path = Some(pico_drive.join("out.uf2"));
             File::create(deployed_path.as_ref().unwrap())?
         } else {
@@ -0,0 +1,241 @@
+use crate::elf::{realize_page, Pages, PAGE_SIZE};
+use crate::uf2::Uf2BlockData;
+use rusb::{Device, DeviceHandle, GlobalContext};
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    io::{Read, Seek},
+    time::Duration,
+};
+use zerocopy::{AsBytes, FromBytes};
+
+const PICOBOOT_VID: u16 = 0x2e8a;
+const PICOBOOT_PID: u16 = 0x0003;
+
+const PICOBOOT_MAGIC: u32 = 0x431f_d10b;
+
+const FLASH_SECTOR_SIZE: u32 = 4096;
+
+// picoboot command ids, from the RP2040/RP2350 PICOBOOT vendor protocol
+const CMD_EXCLUSIVE_ACCESS: u8 = 0x01;
+const CMD_REBOOT: u8 = 0x02;
+const CMD_FLASH_ERASE: u8 = 0x03;
+const CMD_WRITE: u8 = 0x05;
+const CMD_EXIT_XIP: u8 = 0x06;
+
+// vendor control request that resets the PICOBOOT interface's command state
+const PICOBOOT_IF_RESET: u8 = 0x41;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(AsBytes, FromBytes, Debug, Copy, Clone)]
+#[repr(C, packed)]
+struct PicobootCmd {
+    magic: u32,
+    token: u32,
+    cmd_id: u8,
+    cmd_size: u8,
+    _unused: u16,
+    transfer_length: u32,
+    args: [u8; 16],
+}
+
+impl PicobootCmd {
+    fn new(token: u32, cmd_id: u8, args: &[u8], transfer_length: u32) -> PicobootCmd {
+        let mut cmd = PicobootCmd {
+            magic: PICOBOOT_MAGIC,
+            token,
+            cmd_id,
+            cmd_size: args.len().try_into().unwrap(),
+            _unused: 0,
+            transfer_length,
+            args: [0; 16],
+        };
+        cmd.args[..args.len()].copy_from_slice(args);
+        cmd
+    }
+}
+
+struct Picoboot {
+    handle: DeviceHandle<GlobalContext>,
+    interface: u8,
+    ep_out: u8,
+    ep_in: u8,
+    token: u32,
+}
+
+impl Picoboot {
+    fn open() -> Result<Picoboot, Box<dyn Error>> {
+        let device = find_device()?;
+        let config = device.active_config_descriptor()?;
+
+        let (interface, ep_out, ep_in) = config
+            .interfaces()
+            .flat_map(|i| i.descriptors().map(move |d| (i.number(), d)))
+            .find_map(|(number, descriptor)| {
+                if descriptor.class_code() != 0xff {
+                    return None;
+                }
+                let mut ep_out = None;
+                let mut ep_in = None;
+                for endpoint in descriptor.endpoint_descriptors() {
+                    if endpoint.transfer_type() != rusb::TransferType::Bulk {
+                        continue;
+                    }
+                    match endpoint.direction() {
+                        rusb::Direction::Out => ep_out = Some(endpoint.address()),
+                        rusb::Direction::In => ep_in = Some(endpoint.address()),
+                    }
+                }
+                match (ep_out, ep_in) {
+                    (Some(out), Some(inp)) => Some((number, out, inp)),
+                    _ => None,
+                }
+            })
+            .ok_or("Unable to find the PICOBOOT vendor interface")?;
+
+        let mut handle = device.open()?;
+        handle.claim_interface(interface)?;
+
+        handle.write_control(
+            rusb::request_type(
+                rusb::Direction::Out,
+                rusb::RequestType::Vendor,
+                rusb::Recipient::Interface,
+            ),
+            PICOBOOT_IF_RESET,
+            0,
+            interface as u16,
+            &[],
+            TIMEOUT,
+        )?;
+
+        Ok(Picoboot {
+            handle,
+            interface,
+            ep_out,
+            ep_in,
+            token: 1,
+        })
+    }
+
+    fn next_token(&mut self) -> u32 {
+        self.token += 1;
+        self.token
+    }
+
+    fn command(&mut self, cmd_id: u8, args: &[u8], data: &[u8]) -> Result<(), Box<dyn Error>> {
+        let token = self.next_token();
+        let cmd = PicobootCmd::new(token, cmd_id, args, data.len().assert_len());
+
+        self.handle.write_bulk(self.ep_out, cmd.as_bytes(), TIMEOUT)?;
+
+        if !data.is_empty() {
+            self.handle.write_bulk(self.ep_out, data, TIMEOUT)?;
+        }
+
+        // the device acknowledges every command with a zero-length packet;
+        // a stall here means the device rejected the command (e.g. a bad
+        // FLASH_ERASE/WRITE), so surface it instead of pretending it worked
+        let mut ack = [0; 1];
+        self.handle.read_bulk(self.ep_in, &mut ack, TIMEOUT)?;
+
+        Ok(())
+    }
+
+    fn exclusive_access(&mut self, exclusive: u8) -> Result<(), Box<dyn Error>> {
+        self.command(CMD_EXCLUSIVE_ACCESS, &[exclusive], &[])
+    }
+
+    fn exit_xip(&mut self) -> Result<(), Box<dyn Error>> {
+        self.command(CMD_EXIT_XIP, &[], &[])
+    }
+
+    fn flash_erase(&mut self, addr: u32, size: u32) -> Result<(), Box<dyn Error>> {
+        let mut args = [0; 8];
+        args[0..4].copy_from_slice(&addr.to_le_bytes());
+        args[4..8].copy_from_slice(&size.to_le_bytes());
+        self.command(CMD_FLASH_ERASE, &args, &[])
+    }
+
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        let mut args = [0; 8];
+        args[0..4].copy_from_slice(&addr.to_le_bytes());
+        args[4..8].copy_from_slice((data.len() as u32).to_le_bytes().as_slice());
+        self.command(CMD_WRITE, &args, data)
+    }
+
+    fn reboot(&mut self) -> Result<(), Box<dyn Error>> {
+        // pc = 0, sp = 0, delay_ms = 500: let the bootrom pick the reset vector
+        let mut args = [0; 12];
+        args[8..12].copy_from_slice(&500u32.to_le_bytes());
+        self.command(CMD_REBOOT, &args, &[])?;
+        let _ = self.handle.release_interface(self.interface);
+        Ok(())
+    }
+}
+
+trait AssertLen {
+    fn assert_len(self) -> u32;
+}
+
+impl AssertLen for usize {
+    fn assert_len(self) -> u32 {
+        self.try_into().expect("transfer too large for a u32 length")
+    }
+}
+
+fn find_device() -> Result<Device<GlobalContext>, Box<dyn Error>> {
+    for device in rusb::devices()?.iter() {
+        let Ok(descriptor) = device.device_descriptor() else {
+            continue;
+        };
+        if descriptor.vendor_id() == PICOBOOT_VID && descriptor.product_id() == PICOBOOT_PID {
+            return Ok(device);
+        }
+    }
+    Err("Unable to find a pico in BOOTSEL mode over USB".into())
+}
+
+fn flash_sector_aligned_size(addr: u32, size: u32) -> (u32, u32) {
+    let aligned_addr = addr & !(FLASH_SECTOR_SIZE - 1);
+    let aligned_end = (addr + size + FLASH_SECTOR_SIZE - 1) & !(FLASH_SECTOR_SIZE - 1);
+    (aligned_addr, aligned_end - aligned_addr)
+}
+
+/// Flashes the pages computed from the ELF directly over the RP2040/RP2350
+/// PICOBOOT USB interface, without needing the UF2 drive to be mounted.
+/// `erase_flash` should be false for a RAM-style binary, whose pages target
+/// SRAM rather than flash and so must not be erased.
+pub fn deploy(
+    input: &mut (impl Read + Seek),
+    pages: &Pages,
+    erase_flash: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut picoboot = Picoboot::open()?;
+
+    picoboot.exclusive_access(1)?;
+    picoboot.exit_xip()?;
+
+    if erase_flash {
+        let sectors: BTreeSet<u32> = pages
+            .keys()
+            .map(|&page_addr| flash_sector_aligned_size(page_addr, PAGE_SIZE).0)
+            .collect();
+
+        for sector_addr in sectors {
+            picoboot.flash_erase(sector_addr, FLASH_SECTOR_SIZE)?;
+        }
+    }
+
+    for (&page_addr, fragments) in pages {
+        let mut block_data: Uf2BlockData = [0; 476];
+        realize_page(input, fragments, &mut block_data)?;
+        picoboot.write(page_addr, &block_data[..PAGE_SIZE as usize])?;
+    }
+
+    println!("Rebooting pico");
+    picoboot.reboot()?;
+
+    Ok(())
+}
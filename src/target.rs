@@ -0,0 +1,70 @@
+use crate::address_range::{AddressRange, RangeType, RP2040_ADDRESS_RANGES_FLASH, RP2040_ADDRESS_RANGES_RAM};
+use crate::uf2::{
+    ABSOLUTE_FAMILY_ID, DATA_FAMILY_ID, RP2040_FAMILY_ID, RP2350_ARM_S_FAMILY_ID,
+    RP2350_RISCV_FAMILY_ID,
+};
+use clap::ValueEnum;
+
+const RP2350_FLASH_START: u32 = 0x1000_0000;
+const RP2350_FLASH_END: u32 = 0x2000_0000;
+const RP2350_MAIN_RAM_START: u32 = 0x2000_0000;
+const RP2350_MAIN_RAM_END: u32 = 0x2008_2000;
+const RP2350_XIP_SRAM_START: u32 = 0x1800_0000;
+const RP2350_XIP_SRAM_END: u32 = 0x1800_4000;
+
+const RP2350_ADDRESS_RANGES_FLASH: &[AddressRange] = &[
+    AddressRange::new(RP2350_FLASH_START, RP2350_FLASH_END, RangeType::Contents),
+    AddressRange::new(RP2350_MAIN_RAM_START, RP2350_MAIN_RAM_END, RangeType::NoContents),
+];
+
+const RP2350_ADDRESS_RANGES_RAM: &[AddressRange] = &[
+    AddressRange::new(RP2350_MAIN_RAM_START, RP2350_MAIN_RAM_END, RangeType::Contents),
+    AddressRange::new(RP2350_XIP_SRAM_START, RP2350_XIP_SRAM_END, RangeType::Contents),
+];
+
+// `absolute` and `data` blobs are addressed at whatever offset the caller
+// baked into the ELF, so there is no fixed memory map to validate against.
+const ANY_ADDRESS_RANGE: &[AddressRange] = &[AddressRange::new(0, u32::MAX, RangeType::Contents)];
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Rp2040,
+    #[clap(name = "rp2350-arm-s")]
+    Rp2350ArmS,
+    #[clap(name = "rp2350-riscv")]
+    Rp2350Riscv,
+    Absolute,
+    Data,
+}
+
+impl Target {
+    pub fn family_id(&self) -> u32 {
+        match self {
+            Target::Rp2040 => RP2040_FAMILY_ID,
+            Target::Rp2350ArmS => RP2350_ARM_S_FAMILY_ID,
+            Target::Rp2350Riscv => RP2350_RISCV_FAMILY_ID,
+            Target::Absolute => ABSOLUTE_FAMILY_ID,
+            Target::Data => DATA_FAMILY_ID,
+        }
+    }
+
+    pub fn address_ranges(&self, ram_style: bool) -> &'static [AddressRange] {
+        match self {
+            Target::Rp2040 => {
+                if ram_style {
+                    RP2040_ADDRESS_RANGES_RAM
+                } else {
+                    RP2040_ADDRESS_RANGES_FLASH
+                }
+            }
+            Target::Rp2350ArmS | Target::Rp2350Riscv => {
+                if ram_style {
+                    RP2350_ADDRESS_RANGES_RAM
+                } else {
+                    RP2350_ADDRESS_RANGES_FLASH
+                }
+            }
+            Target::Absolute | Target::Data => ANY_ADDRESS_RANGE,
+        }
+    }
+}
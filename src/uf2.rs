@@ -0,0 +1,34 @@
+use zerocopy::{AsBytes, FromBytes};
+
+pub const UF2_MAGIC_START0: u32 = 0x0A32_4655;
+pub const UF2_MAGIC_START1: u32 = 0x9E5D_5157;
+pub const UF2_MAGIC_END: u32 = 0x0AB1_6F30;
+
+pub const UF2_FLAG_FAMILY_ID_PRESENT: u32 = 0x0000_2000;
+
+pub const RP2040_FAMILY_ID: u32 = 0xe48b_ff56;
+pub const DATA_FAMILY_ID: u32 = 0xe48b_ff57;
+pub const ABSOLUTE_FAMILY_ID: u32 = 0xe48b_ff58;
+pub const RP2350_ARM_S_FAMILY_ID: u32 = 0xe48b_ff59;
+pub const RP2350_RISCV_FAMILY_ID: u32 = 0xe48b_ff5a;
+
+pub type Uf2BlockData = [u8; 476];
+
+#[derive(AsBytes, FromBytes, Debug, Copy, Clone)]
+#[repr(C)]
+pub struct Uf2BlockHeader {
+    pub magic_start0: u32,
+    pub magic_start1: u32,
+    pub flags: u32,
+    pub target_addr: u32,
+    pub payload_size: u32,
+    pub block_no: u32,
+    pub num_blocks: u32,
+    pub file_size: u32, // or familyID
+}
+
+#[derive(AsBytes, FromBytes, Debug, Copy, Clone)]
+#[repr(C)]
+pub struct Uf2BlockFooter {
+    pub magic_end: u32,
+}